@@ -6,8 +6,8 @@ use super::{
     TestDefinition, TransformOuter,
 };
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
 use vector_core::config::GlobalOptions;
 use vector_core::default_data_dir;
 use vector_core::transform::TransformConfig;
@@ -35,17 +35,212 @@ pub struct ConfigBuilder {
     pub provider: Option<Box<dyn provider::ProviderConfig>>,
     #[serde(default)]
     pub pipelines: Pipelines,
+    #[serde(default)]
+    pub templates: IndexMap<ComponentId, AliasTemplate>,
+}
+
+/// A reusable transform template that can be composed from other templates and
+/// instantiated as a concrete transform at compile time.
+///
+/// The `uses` list names the templates this one is built on; they are expanded
+/// depth-first and deep-merged in `uses` order, and the template's own
+/// definition is then deep-merged with the result. A scalar that differs
+/// between any of them is reported as a conflict rather than overridden, so
+/// the template's own definition does not take precedence over the templates
+/// it uses. Recursive definitions are rejected during
+/// [`ConfigBuilder::build_with_warnings`].
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AliasTemplate {
+    #[serde(default)]
+    pub uses: Vec<ComponentId>,
+    #[serde(flatten)]
+    pub transform: TransformOuter,
+}
+
+/// Controls how [`ConfigBuilder::append_with`] reconciles two definitions that
+/// share the same component id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// Reject duplicate ids with a "duplicate … found" error. This is the
+    /// behavior of the plain [`ConfigBuilder::append`].
+    Error,
+    /// Let the incoming definition replace the existing one wholesale.
+    Override,
+    /// Merge the two definitions field-by-field, erroring only on conflicting
+    /// scalar values.
+    DeepMerge,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        MergePolicy::Error
+    }
+}
+
+/// Identifies the role a [`ConfigBuilder`] plays in a [`layered`] stack, which
+/// determines how it reconciles global scalars with the layers below it.
+///
+/// `Defaults` and `File` layers are peers: they merge with the existing strict
+/// rules, so two of them disagreeing on a scalar like `data_dir` is still an
+/// error. An `Override` layer sits above them and is allowed to silently
+/// replace scalars that an earlier layer set.
+///
+/// [`layered`]: ConfigBuilder::layered
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerKind {
+    /// Built-in defaults, sitting at the bottom of the stack.
+    Defaults,
+    /// A regular config file fragment.
+    File,
+    /// A final overrides layer that wins over everything below it.
+    Override,
+}
+
+/// The section of a [`ConfigBuilder`] a [`ComponentId`] belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ComponentKind {
+    EnrichmentTable,
+    Source,
+    Transform,
+    Sink,
+}
+
+impl ComponentKind {
+    /// The config section this kind lives under, as used in error messages.
+    pub fn section(self) -> &'static str {
+        match self {
+            ComponentKind::EnrichmentTable => "enrichment_tables",
+            ComponentKind::Source => "sources",
+            ComponentKind::Transform => "transforms",
+            ComponentKind::Sink => "sinks",
+        }
+    }
+}
+
+/// A raw, unvalidated collection of component ids gathered from a
+/// [`ConfigBuilder`]. Call [`validate`] to turn it into a [`ComponentIndex`]
+/// whose ids are guaranteed unique, or [`into_lookup`] for a best-effort index
+/// when duplicates are handled elsewhere.
+///
+/// [`validate`]: ComponentIndexBuilder::validate
+/// [`into_lookup`]: ComponentIndexBuilder::into_lookup
+#[derive(Default)]
+pub struct ComponentIndexBuilder {
+    entries: Vec<(ComponentId, ComponentKind)>,
+}
+
+impl ComponentIndexBuilder {
+    pub fn insert(&mut self, id: ComponentId, kind: ComponentKind) {
+        self.entries.push((id, kind));
+    }
+
+    /// Build a lookup that tolerates duplicates (first definition wins). Use
+    /// this when collisions are diagnosed by the caller, e.g. `merge_pipelines`.
+    pub fn into_lookup(self) -> ComponentIndex {
+        let mut by_id = IndexMap::new();
+        let mut by_name: HashMap<String, Vec<ComponentId>> = HashMap::new();
+        for (id, kind) in self.entries {
+            by_name.entry(id.id().to_string()).or_default().push(id.clone());
+            by_id.entry(id).or_insert(kind);
+        }
+        ComponentIndex { by_id, by_name }
+    }
+
+    /// Validate the whole namespace, producing a [`ComponentIndex`] with
+    /// guaranteed-unique ids or a single aggregated list of errors naming every
+    /// conflicting id, the sections it appears in, and any pipeline-scoped id
+    /// that shadows a global one.
+    pub fn validate(self) -> Result<ComponentIndex, Vec<String>> {
+        let mut by_id: IndexMap<ComponentId, ComponentKind> = IndexMap::new();
+        let mut by_name: HashMap<String, Vec<ComponentId>> = HashMap::new();
+        let mut collisions: IndexMap<ComponentId, Vec<ComponentKind>> = IndexMap::new();
+
+        for (id, kind) in self.entries {
+            by_name.entry(id.id().to_string()).or_default().push(id.clone());
+            match by_id.get(&id) {
+                Some(existing) => {
+                    collisions
+                        .entry(id.clone())
+                        .or_insert_with(|| vec![*existing])
+                        .push(kind);
+                }
+                None => {
+                    by_id.insert(id, kind);
+                }
+            }
+        }
+
+        let mut errors = Vec::new();
+        for (id, kinds) in &collisions {
+            let sections = kinds
+                .iter()
+                .map(|kind| kind.section())
+                .collect::<Vec<_>>()
+                .join(", ");
+            errors.push(format!(
+                "component id '{}' is defined in multiple sections: {}",
+                id, sections
+            ));
+        }
+
+        // A pipeline-scoped id must not shadow a global id of the same name.
+        for (name, ids) in &by_name {
+            let global = ids.iter().any(ComponentId::is_global);
+            if global {
+                for scoped in ids.iter().filter(|id| !id.is_global()) {
+                    errors.push(format!(
+                        "pipeline-scoped component '{}' shadows global component id '{}'",
+                        scoped, name
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(ComponentIndex { by_id, by_name })
+        } else {
+            errors.sort();
+            errors.dedup();
+            Err(errors)
+        }
+    }
+}
+
+/// A validated index from every [`ComponentId`] in a [`ConfigBuilder`] to its
+/// [`ComponentKind`], with a reverse map from an input reference name to the
+/// component(s) it resolves to.
+pub struct ComponentIndex {
+    by_id: IndexMap<ComponentId, ComponentKind>,
+    by_name: HashMap<String, Vec<ComponentId>>,
+}
+
+impl ComponentIndex {
+    /// The kind of a specific component id, if present.
+    pub fn kind(&self, id: &ComponentId) -> Option<ComponentKind> {
+        self.by_id.get(id).copied()
+    }
+
+    /// Whether the index contains this exact component id.
+    pub fn contains(&self, id: &ComponentId) -> bool {
+        self.by_id.contains_key(id)
+    }
+
+    /// The component(s) an input reference name resolves to.
+    pub fn resolve(&self, name: &str) -> &[ComponentId] {
+        self.by_name.get(name).map_or(&[], Vec::as_slice)
+    }
 }
 
 impl Clone for ConfigBuilder {
     fn clone(&self) -> Self {
-        // This is a hack around the issue of cloning
-        // trait objects. So instead to clone the config
-        // we first serialize it into JSON, then back from
-        // JSON. Originally we used TOML here but TOML does not
-        // support serializing `None`.
-        let json = serde_json::to_value(self).unwrap();
-        serde_json::from_value(json).unwrap()
+        // Thin wrapper over `try_clone` kept for backward compatibility; it
+        // expects the round-trip to succeed and panics otherwise. Prefer
+        // `try_clone` anywhere a misbehaving component should be reported.
+        self.try_clone()
+            .expect("config builder failed to round-trip through JSON")
     }
 }
 
@@ -63,31 +258,73 @@ impl From<Config> for ConfigBuilder {
             provider: None,
             tests: c.tests,
             pipelines: Default::default(),
+            templates: Default::default(),
         }
     }
 }
 
 impl ConfigBuilder {
+    /// Clone the builder by serializing to JSON and back — the same trick the
+    /// [`Clone`] impl uses to duplicate the boxed trait objects — but surface
+    /// any serde failure instead of panicking. When a component's
+    /// `Serialize`/`Deserialize` is lossy the error is pinned to the offending
+    /// component id so config reload can degrade gracefully.
+    pub fn try_clone(&self) -> Result<Self, Vec<String>> {
+        match serde_json::to_value(self).and_then(serde_json::from_value) {
+            Ok(cloned) => Ok(cloned),
+            Err(error) => Err(self.diagnose_round_trip(&error)),
+        }
+    }
+
+    // Locate which component(s) fail to round-trip so the caller gets a
+    // diagnosable error rather than an opaque whole-config failure.
+    fn diagnose_round_trip(&self, error: &serde_json::Error) -> Vec<String> {
+        let mut errors = Vec::new();
+        check_round_trip(&self.enrichment_tables, "enrichment_table", &mut errors);
+        check_round_trip(&self.sources, "source", &mut errors);
+        check_round_trip(&self.sinks, "sink", &mut errors);
+        check_round_trip(&self.transforms, "transform", &mut errors);
+        check_round_trip(&self.templates, "template", &mut errors);
+        // The motivating failure is a provider trait object that can't
+        // round-trip, so probe it explicitly rather than letting it fall
+        // through to the generic message below.
+        if self.provider.is_some() {
+            if let Err(error) = serde_json::to_value(&self.provider)
+                .and_then(serde_json::from_value::<Option<Box<dyn provider::ProviderConfig>>>)
+            {
+                errors.push(format!("failed to clone provider: {}", error));
+            }
+        }
+        if errors.is_empty() {
+            errors.push(format!("failed to clone config: {}", error));
+        }
+        errors
+    }
+
     // moves the pipeline transforms into regular scoped transforms
     // and add the output to the sources
     pub fn merge_pipelines(mut self) -> (Self, Vec<String>) {
         let mut errors = Vec::new();
-        let global_transforms = self
-            .transforms
-            .keys()
-            .chain(self.sources.keys())
-            .filter(|id| id.is_global())
-            .map(|id| id.id().to_string())
-            .collect::<HashSet<_>>();
+        let index = self.component_lookup();
         let pipeline_transforms = self.pipelines.into_scoped();
         for (component_id, pipeline_transform) in pipeline_transforms {
-            if global_transforms.contains(component_id.id()) {
+            let shadows_global = index.resolve(component_id.id()).iter().any(|id| {
+                id.is_global()
+                    && matches!(
+                        index.kind(id),
+                        Some(ComponentKind::Source | ComponentKind::Transform)
+                    )
+            });
+            if shadows_global {
                 errors.push(format!(
                     "Component ID '{}' is already used.",
                     component_id.id()
                 ));
                 continue;
             }
+            // Probe the live maps for the input lookup: a later pipeline
+            // transform may reference one inserted earlier in this same loop,
+            // which the pre-loop `index` snapshot wouldn't know about.
             for input in pipeline_transform.outputs.iter() {
                 if let Some(transform) = self.transforms.get_mut(input) {
                     transform.inputs.push(component_id.clone());
@@ -114,11 +351,51 @@ impl ConfigBuilder {
                 tests: self.tests,
                 enrichment_tables: self.enrichment_tables,
                 pipelines: Default::default(),
+                templates: self.templates,
             },
             errors,
         )
     }
 
+    /// Build a validated [`ComponentIndex`] over the whole namespace
+    /// (enrichment tables, sources, sinks, transforms, and scoped pipeline
+    /// transforms). Returns the aggregated collision/shadowing errors when the
+    /// namespace is not internally consistent.
+    pub fn component_index(&self) -> Result<ComponentIndex, Vec<String>> {
+        let mut index = self.raw_component_index();
+        // Collect the scoped pipeline transform ids from a fallible clone so a
+        // component whose config can't round-trip surfaces a diagnosable error
+        // instead of panicking.
+        for (component_id, _) in self.try_clone()?.pipelines.into_scoped() {
+            index.insert(component_id, ComponentKind::Transform);
+        }
+        index.validate()
+    }
+
+    // Best-effort reverse index of the components in the top-level maps (not
+    // the pipelines), used to detect id collisions in `merge_pipelines` and
+    // `expand_templates`.
+    fn component_lookup(&self) -> ComponentIndex {
+        self.raw_component_index().into_lookup()
+    }
+
+    fn raw_component_index(&self) -> ComponentIndexBuilder {
+        let mut index = ComponentIndexBuilder::default();
+        for id in self.enrichment_tables.keys() {
+            index.insert(id.clone(), ComponentKind::EnrichmentTable);
+        }
+        for id in self.sources.keys() {
+            index.insert(id.clone(), ComponentKind::Source);
+        }
+        for id in self.transforms.keys() {
+            index.insert(id.clone(), ComponentKind::Transform);
+        }
+        for id in self.sinks.keys() {
+            index.insert(id.clone(), ComponentKind::Sink);
+        }
+        index
+    }
+
     pub fn build(self) -> Result<Config, Vec<String>> {
         let (config, warnings) = self.build_with_warnings()?;
 
@@ -129,10 +406,73 @@ impl ConfigBuilder {
         Ok(config)
     }
 
-    pub fn build_with_warnings(self) -> Result<(Config, Vec<String>), Vec<String>> {
+    pub fn build_with_warnings(mut self) -> Result<(Config, Vec<String>), Vec<String>> {
+        self.expand_templates()?;
         compiler::compile(self)
     }
 
+    // Expand every alias template into a concrete transform, resolving
+    // composition depth-first and rejecting recursive definitions before the
+    // compiler ever sees them.
+    fn expand_templates(&mut self) -> Result<(), Vec<String>> {
+        if self.templates.is_empty() {
+            return Ok(());
+        }
+
+        let mut resolved = IndexMap::new();
+        let mut errors = Vec::new();
+        let ids = self.templates.keys().cloned().collect::<Vec<_>>();
+        for id in &ids {
+            let mut path = Vec::new();
+            if let Err(error) = expand_alias(&self.templates, id, &mut path, &mut resolved) {
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        // Templates referenced by another template's `uses` are pure building
+        // blocks; only the top-level ones become wired transforms so we don't
+        // inject intermediate-only pipelines into the graph.
+        let building_blocks = self
+            .templates
+            .values()
+            .flat_map(|template| template.uses.iter().cloned())
+            .collect::<std::collections::HashSet<_>>();
+        let existing = self.component_lookup();
+
+        for (id, _) in std::mem::take(&mut self.templates) {
+            if building_blocks.contains(&id) {
+                continue;
+            }
+            // A template must not silently clobber an existing component.
+            if let Some(kind) = existing.kind(&id) {
+                errors.push(format!(
+                    "template '{}' collides with an existing component in {}",
+                    id,
+                    kind.section()
+                ));
+                continue;
+            }
+            let value = resolved.swap_remove(&id).expect("alias resolved above");
+            match serde_json::from_value::<TransformOuter>(value) {
+                Ok(transform) => {
+                    self.transforms.insert(id, transform);
+                }
+                Err(error) => errors.push(format!("alias '{}': {}", id, error)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     pub fn add_enrichment_table<E: EnrichmentTableConfig + 'static, T: Into<String>>(
         &mut self,
         name: T,
@@ -184,7 +524,116 @@ impl ConfigBuilder {
         self.pipelines = pipelines;
     }
 
+    /// Fold an ordered stack of layers into a single builder, low precedence
+    /// first. Component maps union across layers (the last layer to define an
+    /// id wins), while global scalars follow each layer's [`LayerKind`]:
+    /// `Defaults`/`File` layers keep the strict conflict rules of [`append`],
+    /// and an `Override` layer deterministically replaces scalars such as
+    /// `data_dir` or `log_schema` fields that an earlier layer set.
+    ///
+    /// With no `Override` layer present the global scalars follow the same
+    /// strict rules as appending the layers in order; components that share
+    /// an id are still replaced by the later layer rather than rejected.
+    ///
+    /// [`append`]: ConfigBuilder::append
+    pub fn layered(layers: Vec<(LayerKind, ConfigBuilder)>) -> Result<Self, Vec<String>> {
+        let mut errors = Vec::new();
+        let mut base = ConfigBuilder::default();
+
+        for (kind, layer) in layers {
+            match kind {
+                LayerKind::Override => base.apply_override_layer(layer, &mut errors),
+                LayerKind::Defaults | LayerKind::File => {
+                    if let Err(layer_errors) = base.append_with(layer, MergePolicy::Override) {
+                        errors.extend(layer_errors);
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(base)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Apply a higher-precedence overrides layer on top of `self`: global
+    // scalars that the layer sets win over whatever an earlier layer left, and
+    // components are replaced wholesale by id.
+    fn apply_override_layer(&mut self, with: Self, _errors: &mut Vec<String>) {
+        // Merge the api options, but — like `data_dir`/`log_schema` below —
+        // let the overrides layer win on conflict instead of erroring, so an
+        // override that never set `[api]` leaves the lower layer intact.
+        #[cfg(feature = "api")]
+        if self.api.merge(with.api.clone()).is_err() {
+            self.api = with.api;
+        }
+
+        if with.provider.is_some() {
+            self.provider = with.provider;
+        }
+
+        // Unlike `append`, an overrides layer may replace a `data_dir` that an
+        // earlier layer set rather than erroring on the conflict. As in
+        // `append`, an unset (`None`) or platform-default `data_dir` is treated
+        // as not mentioned by the layer, so we leave the lower layers' value
+        // alone; any other value wins.
+        if with.global.data_dir.is_some() && with.global.data_dir != default_data_dir() {
+            self.global.data_dir = with.global.data_dir;
+        }
+
+        // Merge log schemas, but let the overrides layer win on conflict
+        // instead of turning it into an error.
+        if self.global.log_schema.merge(&with.global.log_schema).is_err() {
+            self.global.log_schema = with.global.log_schema;
+        }
+
+        self.healthchecks.merge(with.healthchecks);
+
+        self.enrichment_tables.extend(with.enrichment_tables);
+        self.sources.extend(with.sources);
+        self.sinks.extend(with.sinks);
+        self.transforms.extend(with.transforms);
+
+        for test in with.tests {
+            if let Some(existing) = self.tests.iter_mut().find(|t| t.name == test.name) {
+                *existing = test;
+            } else {
+                self.tests.push(test);
+            }
+        }
+    }
+
     pub fn append(&mut self, with: Self) -> Result<(), Vec<String>> {
+        self.append_with(with, MergePolicy::Error)
+    }
+
+    /// Merge `with` into `self`, reconciling components that share an id
+    /// according to `policy`.
+    ///
+    /// With [`MergePolicy::Error`] this behaves exactly like [`append`]:
+    /// duplicate ids are rejected. [`MergePolicy::Override`] lets the incoming
+    /// component definition win, and [`MergePolicy::DeepMerge`] merges the two
+    /// definitions field-by-field so operators can layer partial overrides
+    /// (e.g. tweak a single sink's `batch` settings from a drop-in file)
+    /// without restating the whole component.
+    ///
+    /// The `policy` governs only how components that share an id are
+    /// reconciled. The global scalars (`api`, `data_dir`, `log_schema`) always
+    /// follow the strict merge rules regardless of `policy`; use
+    /// [`ConfigBuilder::layered`] with an `Override` layer when a
+    /// higher-precedence source should win those.
+    ///
+    /// Under `DeepMerge`, array fields are merged by dedup-union (incoming
+    /// items appended in order, skipping ones already present). That array
+    /// policy is fixed by design — it keeps `inputs` from double-wiring — and
+    /// is not selectable per field, so intentional duplicates in other array
+    /// fields are collapsed.
+    ///
+    /// [`append`]: ConfigBuilder::append
+    /// [`ConfigBuilder::layered`]: ConfigBuilder::layered
+    pub fn append_with(&mut self, with: Self, policy: MergePolicy) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
         #[cfg(feature = "api")]
@@ -212,26 +661,27 @@ impl ConfigBuilder {
 
         self.healthchecks.merge(with.healthchecks);
 
-        with.enrichment_tables.keys().for_each(|k| {
-            if self.enrichment_tables.contains_key(k) {
-                errors.push(format!("duplicate enrichment_table name found: {}", k));
-            }
-        });
-        with.sources.keys().for_each(|k| {
-            if self.sources.contains_key(k) {
-                errors.push(format!("duplicate source id found: {}", k));
-            }
-        });
-        with.sinks.keys().for_each(|k| {
-            if self.sinks.contains_key(k) {
-                errors.push(format!("duplicate sink id found: {}", k));
-            }
-        });
-        with.transforms.keys().for_each(|k| {
-            if self.transforms.contains_key(k) {
-                errors.push(format!("duplicate transform id found: {}", k));
-            }
-        });
+        // Stage the reconciled entries for every component map so that a
+        // failure leaves the component maps and tests untouched (the global
+        // options above have already been merged into `self`).
+        let enrichment_tables = merge_component_map(
+            &self.enrichment_tables,
+            with.enrichment_tables,
+            "enrichment_table name",
+            policy,
+            &mut errors,
+        );
+        let sources =
+            merge_component_map(&self.sources, with.sources, "source id", policy, &mut errors);
+        let sinks = merge_component_map(&self.sinks, with.sinks, "sink id", policy, &mut errors);
+        let transforms = merge_component_map(
+            &self.transforms,
+            with.transforms,
+            "transform id",
+            policy,
+            &mut errors,
+        );
+
         with.tests.iter().for_each(|wt| {
             if self.tests.iter().any(|t| t.name == wt.name) {
                 errors.push(format!("duplicate test name found: {}", wt.name));
@@ -241,10 +691,10 @@ impl ConfigBuilder {
             return Err(errors);
         }
 
-        self.enrichment_tables.extend(with.enrichment_tables);
-        self.sources.extend(with.sources);
-        self.sinks.extend(with.sinks);
-        self.transforms.extend(with.transforms);
+        self.enrichment_tables.extend(enrichment_tables);
+        self.sources.extend(sources);
+        self.sinks.extend(sinks);
+        self.transforms.extend(transforms);
         self.tests.extend(with.tests);
 
         Ok(())
@@ -257,8 +707,266 @@ impl ConfigBuilder {
     }
 }
 
+/// Round-trip each component in a map through JSON, recording the id of any
+/// that fails so a lossy `Serialize`/`Deserialize` can be pinned to its source.
+fn check_round_trip<T>(map: &IndexMap<ComponentId, T>, kind: &str, errors: &mut Vec<String>)
+where
+    T: Serialize + DeserializeOwned,
+{
+    for (id, value) in map {
+        if let Err(error) =
+            serde_json::to_value(value).and_then(serde_json::from_value::<T>)
+        {
+            errors.push(format!("failed to clone {} '{}': {}", kind, id, error));
+        }
+    }
+}
+
+/// Reconcile an incoming component map against the existing one according to
+/// `policy`, returning the entries that should be inserted into the existing
+/// map. New ids always pass through; ids already present are handled per
+/// `policy`. Any conflict is recorded in `errors` and the offending entry is
+/// dropped so the caller can bail out without mutating anything.
+fn merge_component_map<T>(
+    existing: &IndexMap<ComponentId, T>,
+    incoming: IndexMap<ComponentId, T>,
+    kind: &str,
+    policy: MergePolicy,
+    errors: &mut Vec<String>,
+) -> Vec<(ComponentId, T)>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let mut merged = Vec::new();
+    for (id, value) in incoming {
+        match existing.get(&id) {
+            None => merged.push((id, value)),
+            Some(current) => match policy {
+                MergePolicy::Error => {
+                    errors.push(format!("duplicate {} found: {}", kind, id));
+                }
+                MergePolicy::Override => merged.push((id, value)),
+                MergePolicy::DeepMerge => {
+                    if let Some(value) = deep_merge_component(current, value, &id, errors) {
+                        merged.push((id, value));
+                    }
+                }
+            },
+        }
+    }
+    merged
+}
+
+/// Reduce a fully-serialized component to the top-level fields its definition
+/// actually set, dropping any whose value matches what it would take by
+/// default. Nested objects are kept whole, defaulted members included.
+///
+/// Because `append_with` receives already-deserialized components, serde has
+/// filled every unset field with its default; fields without
+/// `skip_serializing_if` then show up in the JSON and would otherwise be merged
+/// as if the drop-in had set them. A field is treated as *carried* when it is
+/// required (removing it stops the component deserializing) or when its value
+/// differs from the default the component takes with that field omitted.
+fn carried_fields<T>(value: serde_json::Value) -> serde_json::Value
+where
+    T: Serialize + DeserializeOwned,
+{
+    let object = match value {
+        serde_json::Value::Object(object) => object,
+        other => return other,
+    };
+
+    let mut carried = serde_json::Map::new();
+    for (key, value) in &object {
+        let mut without = object.clone();
+        without.remove(key);
+        let is_default = match serde_json::from_value::<T>(serde_json::Value::Object(without)) {
+            Ok(defaulted) => serde_json::to_value(defaulted)
+                .ok()
+                .and_then(|defaulted| defaulted.get(key).cloned())
+                .map_or(false, |default_value| &default_value == value),
+            // Removing the field broke deserialization, so it is required and
+            // therefore carried.
+            Err(_) => false,
+        };
+        if !is_default {
+            carried.insert(key.clone(), value.clone());
+        }
+    }
+
+    serde_json::Value::Object(carried)
+}
+
+/// Field-by-field merge of two definitions of the same component id, performed
+/// over their `serde_json::Value` representation (the same JSON round-trip used
+/// by [`ConfigBuilder::clone`]). Only the top-level fields each side actually
+/// carries participate (see [`carried_fields`]), so restating just `type` plus
+/// the one knob a drop-in wants to change doesn't drag defaulted siblings into
+/// the merge, and a field the existing definition left at its default can be
+/// set by the drop-in. Returns `None` and records an error when a scalar field holds
+/// conflicting values or when the merged value fails to deserialize back into
+/// `T`.
+fn deep_merge_component<T>(
+    existing: &T,
+    incoming: T,
+    id: &ComponentId,
+    errors: &mut Vec<String>,
+) -> Option<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let base = match serde_json::to_value(existing) {
+        Ok(value) => value,
+        Err(error) => {
+            errors.push(format!("failed to merge '{}': {}", id, error));
+            return None;
+        }
+    };
+    let other = match serde_json::to_value(&incoming) {
+        Ok(value) => value,
+        Err(error) => {
+            errors.push(format!("failed to merge '{}': {}", id, error));
+            return None;
+        }
+    };
+    let base = carried_fields::<T>(base);
+    let other = carried_fields::<T>(other);
+
+    let before = errors.len();
+    let merged = deep_merge_value(base, other, id, String::new(), errors);
+    if errors.len() != before {
+        return None;
+    }
+
+    match serde_json::from_value(merged) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            errors.push(format!("failed to merge '{}': {}", id, error));
+            None
+        }
+    }
+}
+
+/// Recursively merge `incoming` into `base`. Objects union their keys and merge
+/// recursively, arrays are unioned (incoming items are appended in order,
+/// skipping ones already present so restating `inputs = ["x"]` doesn't
+/// double-wire the graph), and equal scalars collapse to a single value. Two
+/// differing scalars are a conflict: an error naming the component and the
+/// dotted JSON path is pushed and the incoming value is kept so recursion can
+/// continue collecting further conflicts.
+///
+/// When merging components, both sides have already been narrowed to the
+/// top-level fields they actually set (see [`carried_fields`]), so defaulted
+/// siblings don't participate. The array
+/// behavior (dedup-union) is fixed by design rather than configurable; see
+/// [`ConfigBuilder::append_with`].
+fn deep_merge_value(
+    base: serde_json::Value,
+    incoming: serde_json::Value,
+    id: &ComponentId,
+    path: String,
+    errors: &mut Vec<String>,
+) -> serde_json::Value {
+    use serde_json::Value;
+
+    match (base, incoming) {
+        (Value::Object(mut base), Value::Object(incoming)) => {
+            for (key, value) in incoming {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match base.remove(&key) {
+                    Some(existing) => {
+                        base.insert(key, deep_merge_value(existing, value, id, child, errors));
+                    }
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+            Value::Object(base)
+        }
+        (Value::Array(mut base), Value::Array(incoming)) => {
+            for item in incoming {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+            Value::Array(base)
+        }
+        (base, incoming) => {
+            if base == incoming {
+                base
+            } else {
+                errors.push(format!(
+                    "conflicting values for '{}' at field '{}'",
+                    id, path
+                ));
+                incoming
+            }
+        }
+    }
+}
+
+/// Resolve a single alias template into a fully-merged `serde_json::Value` via
+/// depth-first traversal of its `uses` edges. `path` holds the ids currently on
+/// the recursion stack so that re-entering one can be reported as a cycle;
+/// `resolved` memoizes templates that have already been expanded.
+fn expand_alias(
+    templates: &IndexMap<ComponentId, AliasTemplate>,
+    id: &ComponentId,
+    path: &mut Vec<ComponentId>,
+    resolved: &mut IndexMap<ComponentId, serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    if let Some(value) = resolved.get(id) {
+        return Ok(value.clone());
+    }
+
+    if let Some(start) = path.iter().position(|p| p == id) {
+        let mut chain = path[start..]
+            .iter()
+            .map(|p| p.id().to_string())
+            .collect::<Vec<_>>();
+        chain.push(id.id().to_string());
+        return Err(format!(
+            "alias {} has unresolvable recursive definition: {}",
+            id.id(),
+            chain.join(" -> ")
+        ));
+    }
+
+    let template = templates
+        .get(id)
+        .ok_or_else(|| format!("alias '{}' references unknown template", id))?;
+
+    path.push(id.clone());
+
+    let mut errors = Vec::new();
+    let mut merged = serde_json::Value::Object(Default::default());
+    for dependency in &template.uses {
+        let value = expand_alias(templates, dependency, path, resolved)?;
+        merged = deep_merge_value(merged, value, id, String::new(), &mut errors);
+    }
+    match serde_json::to_value(&template.transform) {
+        Ok(own) => merged = deep_merge_value(merged, own, id, String::new(), &mut errors),
+        Err(error) => errors.push(format!("alias '{}': {}", id, error)),
+    }
+
+    path.pop();
+
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+
+    resolved.insert(id.clone(), merged.clone());
+    Ok(merged)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{ComponentKind, LayerKind, MergePolicy};
     use crate::config::pipeline::{Pipeline, Pipelines};
     use crate::config::ConfigBuilder;
     use indexmap::IndexMap;
@@ -376,4 +1084,293 @@ mod tests {
         let config = builder.build().unwrap();
         assert_eq!(config.transforms.len(), 2);
     }
+
+    #[test]
+    fn append_rejects_duplicate_by_default() {
+        let mut base = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+        "#,
+        );
+        let with = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "json"
+        "#,
+        );
+        let errors = base.append(with).unwrap_err();
+        assert_eq!(errors[0], "duplicate source id found: logs");
+    }
+
+    #[test]
+    fn append_with_deep_merge_unions_fields() {
+        let mut base = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+        count = 5
+        "#,
+        );
+        let with = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+        interval = 2.0
+        "#,
+        );
+        base.append_with(with, MergePolicy::DeepMerge).unwrap();
+        let value = serde_json::to_value(&base.sources["logs"]).unwrap();
+        assert_eq!(value["count"], serde_json::json!(5));
+        assert_eq!(value["interval"], serde_json::json!(2.0));
+    }
+
+    #[test]
+    fn append_with_deep_merge_reports_scalar_conflict() {
+        let mut base = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+        "#,
+        );
+        let with = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "json"
+        "#,
+        );
+        let errors = base.append_with(with, MergePolicy::DeepMerge).unwrap_err();
+        assert_eq!(
+            errors[0],
+            "conflicting values for 'logs' at field 'format'"
+        );
+    }
+
+    #[test]
+    fn append_with_deep_merge_ignores_defaulted_fields() {
+        // The base explicitly disables the healthcheck; the drop-in restates
+        // only the component's required fields to tweak it. `healthcheck`
+        // serializes its default, so without carried-field narrowing the
+        // drop-in's defaulted healthcheck would conflict with the base's
+        // explicit one.
+        let mut base = ConfigBuilder::from_toml(
+            r#"
+        [sinks.out]
+        inputs = ["logs"]
+        type = "console"
+        encoding.codec = "json"
+        healthcheck.enabled = false
+        "#,
+        );
+        let with = ConfigBuilder::from_toml(
+            r#"
+        [sinks.out]
+        inputs = ["logs"]
+        type = "console"
+        encoding.codec = "json"
+        "#,
+        );
+        base.append_with(with, MergePolicy::DeepMerge).unwrap();
+        let value = serde_json::to_value(&base.sinks["out"]).unwrap();
+        assert_eq!(value["healthcheck"]["enabled"], serde_json::json!(false));
+    }
+
+    #[test]
+    fn append_with_override_replaces_component() {
+        let mut base = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+        "#,
+        );
+        let with = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "json"
+        "#,
+        );
+        base.append_with(with, MergePolicy::Override).unwrap();
+        let value = serde_json::to_value(&base.sources["logs"]).unwrap();
+        assert_eq!(value["format"], serde_json::json!("json"));
+    }
+
+    #[test]
+    fn layered_override_wins_for_data_dir() {
+        let file = ConfigBuilder::from_toml(
+            r#"
+        data_dir = "/var/lib/vector"
+
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+        "#,
+        );
+        let overrides = ConfigBuilder::from_toml(
+            r#"
+        data_dir = "/srv/vector"
+        "#,
+        );
+        let builder = ConfigBuilder::layered(vec![
+            (LayerKind::File, file),
+            (LayerKind::Override, overrides),
+        ])
+        .unwrap();
+        assert_eq!(
+            builder.global.data_dir,
+            Some(std::path::PathBuf::from("/srv/vector"))
+        );
+        assert!(builder.sources.contains_key(&"logs".into()));
+    }
+
+    #[test]
+    fn layered_peer_files_still_conflict_on_data_dir() {
+        let first = ConfigBuilder::from_toml(r#"data_dir = "/opt/vector""#);
+        let second = ConfigBuilder::from_toml(r#"data_dir = "/srv/vector""#);
+        let errors =
+            ConfigBuilder::layered(vec![(LayerKind::File, first), (LayerKind::File, second)])
+                .unwrap_err();
+        assert_eq!(errors[0], "conflicting values for 'data_dir' found");
+    }
+
+    #[test]
+    fn templates_expand_into_transforms() {
+        let mut builder = ConfigBuilder::from_toml(
+            r#"
+        [templates.base]
+        inputs = ["logs"]
+        type = "remap"
+        source = ""
+
+        [templates.derived]
+        uses = ["base"]
+        inputs = ["logs"]
+        type = "remap"
+        source = ""
+        "#,
+        );
+        builder.expand_templates().unwrap();
+        assert!(builder.templates.is_empty());
+        // `base` is only used via `uses`, so it stays a building block and is
+        // not wired into the graph; only the top-level `derived` is emitted.
+        assert!(!builder.transforms.contains_key(&"base".into()));
+        assert!(builder.transforms.contains_key(&"derived".into()));
+        // Both templates set `inputs = ["logs"]`; the composition must not
+        // double-wire the input.
+        let value = serde_json::to_value(&builder.transforms["derived"]).unwrap();
+        assert_eq!(value["inputs"], serde_json::json!(["logs"]));
+    }
+
+    #[test]
+    fn templates_reject_collision_with_existing_component() {
+        let mut builder = ConfigBuilder::from_toml(
+            r#"
+        [transforms.taken]
+        inputs = ["logs"]
+        type = "remap"
+        source = ""
+
+        [templates.taken]
+        inputs = ["logs"]
+        type = "remap"
+        source = ""
+        "#,
+        );
+        let errors = builder.expand_templates().unwrap_err();
+        assert_eq!(
+            errors[0],
+            "template 'taken' collides with an existing component in transforms"
+        );
+    }
+
+    #[test]
+    fn templates_reject_recursive_definitions() {
+        let mut builder = ConfigBuilder::from_toml(
+            r#"
+        [templates.a]
+        uses = ["b"]
+        inputs = []
+        type = "remap"
+        source = ""
+
+        [templates.b]
+        uses = ["a"]
+        inputs = []
+        type = "remap"
+        source = ""
+        "#,
+        );
+        let errors = builder.expand_templates().unwrap_err();
+        assert_eq!(
+            errors[0],
+            "alias a has unresolvable recursive definition: a -> b -> a"
+        );
+    }
+
+    #[test]
+    fn component_index_resolves_names_and_kinds() {
+        let builder = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+
+        [sinks.out]
+        inputs = ["logs"]
+        type = "console"
+        encoding.codec = "json"
+        "#,
+        );
+        let index = builder.component_index().unwrap();
+        assert_eq!(index.resolve("logs").len(), 1);
+        assert_eq!(index.kind(&"logs".into()), Some(ComponentKind::Source));
+        assert_eq!(index.kind(&"out".into()), Some(ComponentKind::Sink));
+    }
+
+    #[test]
+    fn component_index_reports_cross_section_collision() {
+        let builder = ConfigBuilder::from_toml(
+            r#"
+        [sources.dup]
+        type = "generator"
+        format = "syslog"
+
+        [sinks.dup]
+        inputs = ["dup"]
+        type = "console"
+        encoding.codec = "json"
+        "#,
+        );
+        let errors = builder.component_index().unwrap_err();
+        assert_eq!(
+            errors[0],
+            "component id 'dup' is defined in multiple sections: sources, sinks"
+        );
+    }
+
+    #[test]
+    fn try_clone_round_trips() {
+        let builder = ConfigBuilder::from_toml(
+            r#"
+        [sources.logs]
+        type = "generator"
+        format = "syslog"
+
+        [sinks.out]
+        inputs = ["logs"]
+        type = "console"
+        encoding.codec = "json"
+        "#,
+        );
+        let cloned = builder.try_clone().unwrap();
+        assert_eq!(cloned.sources.len(), builder.sources.len());
+        assert_eq!(cloned.sinks.len(), builder.sinks.len());
+    }
 }